@@ -0,0 +1,4 @@
+pub mod errors;
+pub mod options;
+pub mod secret;
+pub mod util;
@@ -0,0 +1,75 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Shared helpers for wiping secret key material once it goes out of scope.
+//!
+//! Every `hazardous` type that carries raw secret bytes (`SecretKey`, `OneTimeKey`, `Salt`, ...)
+//! should call [`wipe`] from its `Drop` impl, and implement `Debug` by hand rather than
+//! deriving it, so that accidentally logging the value prints [`REDACTED_DEBUG`] instead of the
+//! raw bytes:
+//!
+//! ```ignore
+//! impl Drop for SecretKey {
+//!     fn drop(&mut self) {
+//!         secret::wipe(&mut self.value);
+//!     }
+//! }
+//!
+//! impl fmt::Debug for SecretKey {
+//!     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+//!         write!(f, "SecretKey {{ {} bytes, value: {} }}", self.value.len(), secret::REDACTED_DEBUG)
+//!     }
+//! }
+//! ```
+//!
+//! Applied to every secret-bearing type in `hazardous`: `hkdf::{Salt, Prk}`, `mac::hmac::SecretKey`,
+//! `mac::poly1305::OneTimeKey`, and `stream::{chacha20, xchacha20}::SecretKey`.
+
+use std::ptr;
+use std::sync::atomic;
+
+/// The placeholder a secret-bearing type's `Debug` impl should print in place of its bytes.
+pub const REDACTED_DEBUG: &str = "REDACTED";
+
+/// Overwrite `bytes` with zeroes using a volatile write, so the compiler cannot optimize the
+/// write away even though the buffer is about to be dropped.
+pub fn wipe(bytes: &mut [u8]) {
+
+    for byte in bytes.iter_mut() {
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
+
+    atomic::fence(atomic::Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod test {
+
+    use core::secret::wipe;
+
+    #[test]
+    fn wipe_zeroes_buffer() {
+        let mut buf = vec![0x61u8; 32];
+        wipe(&mut buf);
+        assert_eq!(buf, vec![0u8; 32]);
+    }
+}
@@ -24,12 +24,21 @@
 
 
 
+extern crate base64;
+
 use hmac::Hmac;
 use hkdf::Hkdf;
 use pbkdf2::Pbkdf2;
 use core::{errors, util};
 use core::options::ShaVariantOption;
 
+/// The PBKDF2-HMAC-SHA512 iteration count used by `password_hash`/`password_hash_verify`.
+const PASSWORD_HASH_ITERATIONS: usize = 512_000;
+/// The length, in bytes, of the randomly generated salt embedded in a `password_hash` string.
+const PASSWORD_HASH_SALT_LEN: usize = 16;
+/// The length, in bytes, of the derived key embedded in a `password_hash` string.
+const PASSWORD_HASH_DK_LEN: usize = 64;
+
 /// HMAC with SHA512.
 /// # Exceptions:
 /// An exception will be thrown if:
@@ -203,9 +212,108 @@ pub fn pbkdf2_verify(derived_password: &[u8], password: &[u8], salt: &[u8],
     util::compare_ct(&own_pbkdf2, derived_password)
 }
 
+/// Hash a password into a single, self-describing PHC-format string using PBKDF2-HMAC-SHA512.
+/// A random 16 byte salt is generated internally, so callers don't need to manage salts
+/// themselves.
+/// # Usage example:
+///
+/// ```
+/// use orion::default;
+///
+/// let hash = default::password_hash("Secret password".as_bytes()).unwrap();
+/// assert_eq!(default::password_hash_verify(&hash, "Secret password".as_bytes()).unwrap(), true);
+/// ```
+pub fn password_hash(password: &[u8]) -> Result<String, errors::UnknownCryptoError> {
+
+    let salt = util::gen_rand_key(PASSWORD_HASH_SALT_LEN).unwrap();
+
+    let pbkdf2_sha512_res = Pbkdf2 {
+        password: password.to_vec(),
+        salt: salt.clone(),
+        iterations: PASSWORD_HASH_ITERATIONS,
+        length: PASSWORD_HASH_DK_LEN,
+        hmac: ShaVariantOption::SHA512
+    };
+
+    let derived_key = pbkdf2_sha512_res.pbkdf2_compute().unwrap();
+
+    Ok(format!(
+        "$pbkdf2-sha512$i={}${}${}",
+        PASSWORD_HASH_ITERATIONS,
+        base64::encode(&salt),
+        base64::encode(&derived_key)
+    ))
+}
+
+/// Verify a password against a PHC-format string produced by `password_hash`, in constant time.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `hash_string` is not a well-formed `$pbkdf2-sha512$i=<N>$<salt>$<dk>` string
+/// - The salt or derived key fields are not valid base64
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::default;
+///
+/// let hash = default::password_hash("Secret password".as_bytes()).unwrap();
+/// assert_eq!(default::password_hash_verify(&hash, "Secret password".as_bytes()).unwrap(), true);
+/// ```
+pub fn password_hash_verify(hash_string: &str, password: &[u8]) ->
+        Result<bool, errors::UnknownCryptoError> {
+
+    let fields: Vec<&str> = hash_string.split('$').collect();
+
+    // `hash_string` splits into ["", "pbkdf2-sha512", "i=<N>", "<salt>", "<dk>"]
+    if fields.len() != 5 || fields[1] != "pbkdf2-sha512" {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    if !fields[2].starts_with("i=") {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let iterations: usize = match fields[2][2..].parse() {
+        Ok(val) => val,
+        Err(_) => return Err(errors::UnknownCryptoError),
+    };
+    let salt = match base64::decode(fields[3]) {
+        Ok(val) => val,
+        Err(_) => return Err(errors::UnknownCryptoError),
+    };
+    let expected_dk = match base64::decode(fields[4]) {
+        Ok(val) => val,
+        Err(_) => return Err(errors::UnknownCryptoError),
+    };
+
+    // Reject a crafted string carrying a degenerate salt or derived key before deriving
+    // anything from it: a zero-length `expected_dk` would otherwise make `compare_ct` compare
+    // two empty slices and report a match for any password.
+    if salt.len() < PASSWORD_HASH_SALT_LEN {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    if expected_dk.len() != PASSWORD_HASH_DK_LEN {
+        return Err(errors::UnknownCryptoError);
+    }
+
+    let pbkdf2_sha512_res = Pbkdf2 {
+        password: password.to_vec(),
+        salt,
+        iterations,
+        length: expected_dk.len(),
+        hmac: ShaVariantOption::SHA512
+    };
+
+    let own_dk = pbkdf2_sha512_res.pbkdf2_compute().unwrap();
+
+    util::compare_ct(&own_dk, &expected_dk)
+}
+
 #[cfg(test)]
 mod test {
 
+    extern crate base64;
     extern crate hex;
     use self::hex::decode;
     use default;
@@ -307,4 +415,76 @@ mod test {
         default::pbkdf2(&vec![0x61; 10], &vec![0x61; 67], 64).unwrap();
         default::pbkdf2(&vec![0x61; 10], &vec![0x61; 64], 64).unwrap();
     }
+
+    #[test]
+    fn password_hash_verify() {
+
+        let password = "Secret password".as_bytes();
+
+        let hash = default::password_hash(password).unwrap();
+
+        assert_eq!(default::password_hash_verify(&hash, password).unwrap(), true);
+    }
+
+    #[test]
+    fn password_hash_verify_wrong_password() {
+
+        let hash = default::password_hash("Secret password".as_bytes()).unwrap();
+
+        assert!(default::password_hash_verify(&hash, "Wrong password".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn password_hash_is_phc_formatted() {
+
+        let hash = default::password_hash("Secret password".as_bytes()).unwrap();
+        let fields: Vec<&str> = hash.split('$').collect();
+
+        assert_eq!(fields.len(), 5);
+        assert_eq!(fields[1], "pbkdf2-sha512");
+        assert_eq!(fields[2], "i=512000");
+    }
+
+    #[test]
+    fn password_hash_verify_malformed_string() {
+        assert!(default::password_hash_verify("not-a-phc-string", "Secret password".as_bytes()).is_err());
+        assert!(default::password_hash_verify("$pbkdf2-sha512$i=512000$", "Secret password".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn password_hash_verify_rejects_empty_derived_key_field() {
+        // A crafted string with an empty final field must not be accepted as verifying against
+        // any password: `expected_dk` would otherwise decode to zero bytes and trivially match.
+        let forged = "$pbkdf2-sha512$i=512000$AAAAAAAAAAAAAAAAAAAAAA==$";
+
+        assert!(default::password_hash_verify(forged, "Secret password".as_bytes()).is_err());
+        assert!(default::password_hash_verify(forged, "anything else".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn password_hash_verify_rejects_short_salt() {
+        // A salt shorter than the 16 bytes `password_hash` always generates is also rejected,
+        // even when a well-formed (non-empty) derived key is supplied.
+        let forged = format!(
+            "$pbkdf2-sha512$i=512000${}${}",
+            base64::encode(&[0x01; 8]),
+            base64::encode(&[0x02; 64])
+        );
+
+        assert!(default::password_hash_verify(&forged, "Secret password".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn password_hash_verify_rejects_derived_key_of_wrong_length() {
+        // A derived-key field shorter than the 64 bytes `password_hash` always generates must
+        // not be accepted, even when it's non-empty: accepting it would silently re-derive (and
+        // compare) a weaker key than the one `password_hash` committed to.
+        let forged = format!(
+            "$pbkdf2-sha512$i=512000${}${}",
+            base64::encode(&[0x01; 16]),
+            base64::encode(&[0x02; 20])
+        );
+
+        assert!(default::password_hash_verify(&forged, "Secret password".as_bytes()).is_err());
+    }
 }
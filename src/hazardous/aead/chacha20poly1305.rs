@@ -0,0 +1,242 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! ChaCha20-Poly1305 AEAD, as specified in RFC 8439, built on the existing `chacha20` stream
+//! cipher and `poly1305` MAC.
+
+use core::errors::UnknownCryptoError;
+use core::util;
+use hazardous::mac::poly1305::{self, OneTimeKey};
+use hazardous::stream::chacha20::{self, Nonce, SecretKey};
+
+/// The length, in bytes, of the Poly1305 tag appended to the ciphertext.
+pub const TAG_SIZE: usize = 16;
+
+fn pad16(len: usize) -> usize {
+    (16 - (len % 16)) % 16
+}
+
+fn one_time_key(secret_key: &SecretKey, nonce: &Nonce) -> Result<OneTimeKey, UnknownCryptoError> {
+
+    let zeros = [0u8; 32];
+    let mut block = [0u8; 32];
+    chacha20::encrypt(secret_key, nonce, 0, &zeros, &mut block)?;
+
+    OneTimeKey::from_slice(&block)
+}
+
+fn tag(otk: &OneTimeKey, aad: &[u8], ciphertext: &[u8]) -> Result<[u8; TAG_SIZE], UnknownCryptoError> {
+
+    let mut mac = poly1305::init(otk)?;
+
+    mac.update(aad)?;
+    mac.update(&vec![0u8; pad16(aad.len())])?;
+    mac.update(ciphertext)?;
+    mac.update(&vec![0u8; pad16(ciphertext.len())])?;
+    mac.update(&(aad.len() as u64).to_le_bytes())?;
+    mac.update(&(ciphertext.len() as u64).to_le_bytes())?;
+
+    mac.finalize()
+}
+
+/// Encrypt `plaintext` and authenticate it together with `aad`, writing `plaintext.len() +
+/// TAG_SIZE` bytes of ciphertext-then-tag into `out`.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `out` is not exactly `plaintext.len() + TAG_SIZE` bytes long
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::aead::chacha20poly1305;
+/// use orion::hazardous::stream::chacha20::{SecretKey, Nonce};
+///
+/// let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+/// let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+/// let plaintext = "Some secret message.".as_bytes();
+///
+/// let mut out = vec![0u8; plaintext.len() + chacha20poly1305::TAG_SIZE];
+/// chacha20poly1305::seal(&key, &nonce, b"", plaintext, &mut out).unwrap();
+/// ```
+pub fn seal(secret_key: &SecretKey, nonce: &Nonce, aad: &[u8], plaintext: &[u8], out: &mut [u8]) ->
+        Result<(), UnknownCryptoError> {
+
+    if out.len() != plaintext.len() + TAG_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let otk = one_time_key(secret_key, nonce)?;
+
+    let (ciphertext_out, tag_out) = out.split_at_mut(plaintext.len());
+    chacha20::encrypt(secret_key, nonce, 1, plaintext, ciphertext_out)?;
+
+    let computed_tag = tag(&otk, aad, ciphertext_out)?;
+    tag_out.copy_from_slice(&computed_tag);
+
+    Ok(())
+}
+
+/// Verify and decrypt `ciphertext_and_tag`, writing the plaintext into `out`. Refuses to write
+/// any plaintext if the tag does not match.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `ciphertext_and_tag` is shorter than `TAG_SIZE`
+/// - `out` is not exactly `ciphertext_and_tag.len() - TAG_SIZE` bytes long
+/// - The tag embedded in `ciphertext_and_tag` does not authenticate
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::aead::chacha20poly1305;
+/// use orion::hazardous::stream::chacha20::{SecretKey, Nonce};
+///
+/// let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+/// let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+/// let plaintext = "Some secret message.".as_bytes();
+///
+/// let mut sealed = vec![0u8; plaintext.len() + chacha20poly1305::TAG_SIZE];
+/// chacha20poly1305::seal(&key, &nonce, b"", plaintext, &mut sealed).unwrap();
+///
+/// let mut opened = vec![0u8; plaintext.len()];
+/// chacha20poly1305::open(&key, &nonce, b"", &sealed, &mut opened).unwrap();
+/// assert_eq!(opened, plaintext);
+/// ```
+pub fn open(secret_key: &SecretKey, nonce: &Nonce, aad: &[u8], ciphertext_and_tag: &[u8],
+    out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+
+    if ciphertext_and_tag.len() < TAG_SIZE {
+        return Err(UnknownCryptoError);
+    }
+
+    let ciphertext_len = ciphertext_and_tag.len() - TAG_SIZE;
+
+    if out.len() != ciphertext_len {
+        return Err(UnknownCryptoError);
+    }
+
+    let (ciphertext, expected_tag) = ciphertext_and_tag.split_at(ciphertext_len);
+
+    let otk = one_time_key(secret_key, nonce)?;
+    let computed_tag = tag(&otk, aad, ciphertext)?;
+
+    if !util::compare_ct(&computed_tag, expected_tag)? {
+        return Err(UnknownCryptoError);
+    }
+
+    chacha20::decrypt(secret_key, nonce, 1, ciphertext, out)
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::aead::chacha20poly1305::{open, seal, TAG_SIZE};
+    use hazardous::stream::chacha20::{Nonce, SecretKey};
+
+    #[test]
+    // RFC 8439 section 2.8.2.
+    fn rfc8439_seal_test_vector() {
+        let key = SecretKey::from_slice(&decode(
+            "808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f"
+        ).unwrap()).unwrap();
+        let nonce = Nonce::from_slice(&decode("070000004041424344454647").unwrap()).unwrap();
+        let aad = decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only \
+one tip for the future, sunscreen would be it.";
+
+        let mut sealed = vec![0u8; plaintext.len() + TAG_SIZE];
+        seal(&key, &nonce, &aad, plaintext, &mut sealed).unwrap();
+
+        assert_eq!(
+            sealed,
+            decode(
+                "d31a8d34648e60db7b86afbc53ef7ec2\
+                 a4aded51296e08fea9e2b5a736ee62d6\
+                 3dbea45e8ca9671282fafb69da92728b\
+                 1a71de0a9e060b2905d6a5b67ecd3b36\
+                 92ddbd7f2d778b8c9803aee328091b58\
+                 fab324e4fad675945585808b4831d7bc\
+                 3ff4def08e4b7a9de576d26586cec64b\
+                 61161ae10b594f09e26a7e902ecbd060\
+                 0691"
+            ).unwrap()
+        );
+
+        let mut opened = vec![0u8; plaintext.len()];
+        open(&key, &nonce, &aad, &sealed, &mut opened).unwrap();
+        assert_eq!(opened, plaintext.to_vec());
+    }
+
+    #[test]
+    fn seal_then_open_roundtrips() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut sealed = vec![0u8; plaintext.len() + TAG_SIZE];
+        seal(&key, &nonce, b"aad", plaintext, &mut sealed).unwrap();
+
+        let mut opened = vec![0u8; plaintext.len()];
+        open(&key, &nonce, b"aad", &sealed, &mut opened).unwrap();
+
+        assert_eq!(opened, plaintext);
+    }
+
+    #[test]
+    fn open_rejects_tampered_ciphertext() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut sealed = vec![0u8; plaintext.len() + TAG_SIZE];
+        seal(&key, &nonce, b"aad", plaintext, &mut sealed).unwrap();
+        sealed[0] ^= 0x01;
+
+        let mut opened = vec![0u8; plaintext.len()];
+        assert!(open(&key, &nonce, b"aad", &sealed, &mut opened).is_err());
+    }
+
+    #[test]
+    fn open_rejects_wrong_aad() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut sealed = vec![0u8; plaintext.len() + TAG_SIZE];
+        seal(&key, &nonce, b"aad", plaintext, &mut sealed).unwrap();
+
+        let mut opened = vec![0u8; plaintext.len()];
+        assert!(open(&key, &nonce, b"different aad", &sealed, &mut opened).is_err());
+    }
+
+    #[test]
+    fn seal_rejects_wrong_out_length() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut out = vec![0u8; plaintext.len()];
+        assert!(seal(&key, &nonce, b"", plaintext, &mut out).is_err());
+    }
+}
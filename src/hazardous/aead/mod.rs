@@ -0,0 +1,2 @@
+pub mod chacha20poly1305;
+pub mod xchacha20poly1305;
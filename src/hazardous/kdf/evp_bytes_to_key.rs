@@ -0,0 +1,213 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! `EVP_BytesToKey`, the non-standard key-and-IV derivation used by `openssl enc`.
+//!
+//! This exists strictly to decrypt data produced by older OpenSSL tooling. It is not a
+//! general-purpose KDF: it has none of the iteration-count or memory-hardness guarantees of
+//! `pbkdf2` or `scrypt`, and should never be used in new designs.
+
+extern crate md5;
+extern crate sha2;
+
+use self::sha2::{Digest, Sha256, Sha384, Sha512};
+use core::errors::UnknownCryptoError;
+use core::options::ShaVariantOption;
+
+/// The digest `evp_bytes_to_key` hashes each block with.
+pub enum EvpDigest {
+    /// One of the SHA-2 variants already used throughout `hazardous`.
+    Sha(ShaVariantOption),
+    /// The historical MD5 digest OpenSSL defaulted to prior to 1.1.0. Only selected when asked
+    /// for explicitly; never the default.
+    Md5,
+}
+
+fn digest(data: &[u8], which: &EvpDigest) -> Vec<u8> {
+    match *which {
+        EvpDigest::Md5 => md5::compute(data).to_vec(),
+        // `openssl enc` hashes each block with a *plain* digest, not HMAC, so this must not
+        // route through `Hmac`.
+        EvpDigest::Sha(ShaVariantOption::SHA256) => Sha256::digest(data).to_vec(),
+        EvpDigest::Sha(ShaVariantOption::SHA384) => Sha384::digest(data).to_vec(),
+        EvpDigest::Sha(ShaVariantOption::SHA512) => Sha512::digest(data).to_vec(),
+    }
+}
+
+/// Derive a `(key, iv)` pair the way `openssl enc` does, for decrypting legacy ciphertext.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `count` is zero
+/// - Both `key_len` and `iv_len` are zero
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::kdf::evp_bytes_to_key::{evp_bytes_to_key, EvpDigest};
+/// use orion::core::options::ShaVariantOption;
+///
+/// let (key, iv) = evp_bytes_to_key(
+///     b"Secret password",
+///     Some(&[0x01; 8]),
+///     1,
+///     32,
+///     16,
+///     EvpDigest::Sha(ShaVariantOption::SHA256),
+/// ).unwrap();
+/// ```
+pub fn evp_bytes_to_key(password: &[u8], salt: Option<&[u8; 8]>, count: usize, key_len: usize,
+    iv_len: usize, which: EvpDigest) -> Result<(Vec<u8>, Vec<u8>), UnknownCryptoError> {
+
+    if count == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    if key_len == 0 && iv_len == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let salt_bytes: &[u8] = match salt {
+        Some(s) => s,
+        None => b"",
+    };
+
+    let mut derived = Vec::with_capacity(key_len + iv_len);
+    let mut previous_block: Vec<u8> = Vec::new();
+
+    while derived.len() < key_len + iv_len {
+        let mut data = previous_block.clone();
+        data.extend_from_slice(password);
+        data.extend_from_slice(salt_bytes);
+
+        let mut block = digest(&data, &which);
+
+        for _ in 1..count {
+            block = digest(&block, &which);
+        }
+
+        derived.extend_from_slice(&block);
+        previous_block = block;
+    }
+
+    let iv = derived[key_len..key_len + iv_len].to_vec();
+    derived.truncate(key_len);
+
+    Ok((derived, iv))
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::kdf::evp_bytes_to_key::{evp_bytes_to_key, EvpDigest};
+    use core::options::ShaVariantOption;
+
+    #[test]
+    fn count_cannot_be_zero() {
+        assert!(evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 0, 32, 16, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).is_err());
+    }
+
+    #[test]
+    fn key_and_iv_cannot_both_be_zero_length() {
+        assert!(evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 0, 0, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).is_err());
+    }
+
+    #[test]
+    fn produces_requested_lengths() {
+        let (key, iv) = evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 32, 16, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).unwrap();
+
+        assert_eq!(key.len(), 32);
+        assert_eq!(iv.len(), 16);
+    }
+
+    #[test]
+    fn is_deterministic() {
+        let first = evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 32, 16, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).unwrap();
+        let second = evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 32, 16, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).unwrap();
+
+        assert_eq!(first.0, second.0);
+        assert_eq!(first.1, second.1);
+    }
+
+    #[test]
+    fn md5_variant_differs_from_sha256() {
+        let md5_out = evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 32, 16, EvpDigest::Md5
+        ).unwrap();
+        let sha_out = evp_bytes_to_key(
+            b"password", Some(&[0x01; 8]), 1, 32, 16, EvpDigest::Sha(ShaVariantOption::SHA256)
+        ).unwrap();
+
+        assert_ne!(md5_out.0, sha_out.0);
+    }
+
+    // Captured with:
+    //   openssl enc -aes-256-cbc -md sha256 -S 0101010101010101 -k password -P
+    #[test]
+    fn openssl_interop_vector_sha256() {
+        let (key, iv) = evp_bytes_to_key(
+            b"password",
+            Some(&[0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01]),
+            1,
+            32,
+            16,
+            EvpDigest::Sha(ShaVariantOption::SHA256),
+        ).unwrap();
+
+        assert_eq!(to_hex(&key), "ba33aeb3e77b20a4445d6fe7294adec57753a2e85c98fe563ac15ecfc1a023f0");
+        assert_eq!(to_hex(&iv), "9631501307e979818c8b4156f5e08dc8");
+    }
+
+    // Captured with:
+    //   openssl enc -aes-256-cbc -md md5 -S 0101010101010101 -k password -P
+    #[test]
+    fn openssl_interop_vector_md5() {
+        let (key, iv) = evp_bytes_to_key(
+            b"password",
+            Some(&[0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01]),
+            1,
+            32,
+            16,
+            EvpDigest::Md5,
+        ).unwrap();
+
+        assert_eq!(to_hex(&key), "c4b2e1bf53f530978f723c08525b2272da8a2d49f44fc414be926b34181f22cc");
+        assert_eq!(to_hex(&iv), "dc2eed17e73704d46dd38326a0781288");
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len() * 2);
+        for b in bytes {
+            out.push_str(&format!("{:02x}", b));
+        }
+        out
+    }
+}
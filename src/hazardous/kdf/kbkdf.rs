@@ -0,0 +1,164 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! NIST SP 800-108 counter-mode key-based KDF (KBKDF), built on HMAC-SHA512.
+
+use core::errors::UnknownCryptoError;
+use core::options::ShaVariantOption;
+use hmac::Hmac;
+
+/// HMAC-SHA512 produces a 64-byte output block.
+const HMAC_SHA512_OUTSIZE: usize = 64;
+/// NIST SP 800-108 requires the counter to fit in 32 bits, so the KDF cannot be asked to produce
+/// more than `2^32 - 1` output blocks (the counter starts at 1, so the highest valid value is
+/// `u32::MAX` itself, not one less than it).
+const MAX_COUNTER_BLOCKS: u64 = u32::max_value() as u64;
+/// The encoded bit-length of the output, `[L]_2`, must itself fit in 32 bits (SP 800-108 fixes
+/// its width at `r = 32`), so `out` cannot be longer than this many bytes.
+const MAX_OUT_LEN: usize = (u32::max_value() / 8) as usize;
+
+fn be_u32(n: u32) -> [u8; 4] {
+    [(n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8]
+}
+
+/// Derive a key using the NIST SP 800-108 counter-mode KDF with HMAC-SHA512 as the PRF.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The length of `out` requires more than `2^32 - 1` counter blocks
+/// - The length of `out`, in bits, does not fit in the 32-bit `[L]_2` encoding
+/// - The length of `out` is zero
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::kdf::kbkdf;
+///
+/// let key_derivation_key = "Key derivation key".as_bytes();
+/// let label = "Some label".as_bytes();
+/// let context = "Some context".as_bytes();
+///
+/// let mut out = [0u8; 64];
+/// kbkdf::derive_key(key_derivation_key, label, context, &mut out).unwrap();
+/// ```
+pub fn derive_key(key_derivation_key: &[u8], label: &[u8], context: &[u8], out: &mut [u8]) ->
+        Result<(), UnknownCryptoError> {
+
+    if out.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    if out.len() > MAX_OUT_LEN {
+        return Err(UnknownCryptoError);
+    }
+
+    let blocks_needed = (out.len() + HMAC_SHA512_OUTSIZE - 1) / HMAC_SHA512_OUTSIZE;
+
+    if blocks_needed as u64 > MAX_COUNTER_BLOCKS {
+        return Err(UnknownCryptoError);
+    }
+
+    let l_bits = be_u32((out.len() as u32) * 8);
+
+    let mut derived: Vec<u8> = Vec::with_capacity(blocks_needed * HMAC_SHA512_OUTSIZE);
+
+    for i in 1..=(blocks_needed as u32) {
+        let mut data = Vec::with_capacity(4 + label.len() + 1 + context.len() + 4);
+        data.extend_from_slice(&be_u32(i));
+        data.extend_from_slice(label);
+        data.push(0x00);
+        data.extend_from_slice(context);
+        data.extend_from_slice(&l_bits);
+
+        let block = Hmac {
+            secret_key: key_derivation_key.to_vec(),
+            message: data,
+            sha2: ShaVariantOption::SHA512,
+        };
+
+        derived.extend_from_slice(&block.hmac_compute());
+    }
+
+    out.copy_from_slice(&derived[..out.len()]);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::kdf::kbkdf;
+
+    #[test]
+    fn out_cannot_be_empty() {
+        let mut out: [u8; 0] = [];
+        assert!(kbkdf::derive_key(b"kdk", b"label", b"context", &mut out).is_err());
+    }
+
+    #[test]
+    // The counter starts at 1, so the highest valid counter value -- and so the highest number
+    // of blocks the KDF can produce -- is `u32::MAX` itself, not one less than it.
+    fn max_counter_blocks_matches_spec() {
+        assert_eq!(super::MAX_COUNTER_BLOCKS, u64::from(u32::max_value()));
+    }
+
+    #[test]
+    // `out`'s bit-length is encoded as a 32-bit `[L]_2` field; anything longer would silently
+    // truncate when encoded, so the byte-length bound must stay `u32::MAX / 8` rather than
+    // something larger such as `u32::MAX`.
+    fn max_out_len_does_not_overflow_l_bits_encoding() {
+        assert_eq!(super::MAX_OUT_LEN as u64 * 8, u64::from(u32::max_value()) - 7);
+        assert!((super::MAX_OUT_LEN as u64 + 1) * 8 > u64::from(u32::max_value()));
+    }
+
+    #[test]
+    fn derive_key_is_deterministic() {
+        let mut first = [0u8; 128];
+        let mut second = [0u8; 128];
+
+        kbkdf::derive_key(b"key derivation key", b"label", b"context", &mut first).unwrap();
+        kbkdf::derive_key(b"key derivation key", b"label", b"context", &mut second).unwrap();
+
+        assert_eq!(first[..], second[..]);
+    }
+
+    #[test]
+    fn output_spans_multiple_counter_blocks() {
+        let mut out = [0u8; 200];
+        kbkdf::derive_key(b"key derivation key", b"label", b"context", &mut out).unwrap();
+        // 200 bytes requires 4 HMAC-SHA512 blocks; none of them should be all-zero.
+        assert!(out[..64] != [0u8; 64][..]);
+        assert!(out[64..128] != [0u8; 64][..]);
+        assert!(out[128..192] != [0u8; 64][..]);
+        assert!(out[192..] != [0u8; 8][..]);
+    }
+
+    #[test]
+    fn different_labels_produce_different_output() {
+        let mut first = [0u8; 64];
+        let mut second = [0u8; 64];
+
+        kbkdf::derive_key(b"key derivation key", b"label-a", b"context", &mut first).unwrap();
+        kbkdf::derive_key(b"key derivation key", b"label-b", b"context", &mut second).unwrap();
+
+        assert_ne!(first[..], second[..]);
+    }
+}
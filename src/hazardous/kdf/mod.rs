@@ -0,0 +1,4 @@
+pub mod evp_bytes_to_key;
+pub mod hkdf;
+pub mod kbkdf;
+pub mod scrypt;
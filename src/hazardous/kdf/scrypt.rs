@@ -0,0 +1,280 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! scrypt, a memory-hard password-based key derivation function, as specified in RFC 7914.
+
+use core::errors::UnknownCryptoError;
+use core::options::ShaVariantOption;
+use pbkdf2::Pbkdf2;
+
+/// The Salsa20/8 core, operating on sixteen 32-bit words in place.
+fn salsa20_8(block: &mut [u32; 16]) {
+
+    let mut x = *block;
+
+    for _ in 0..4 {
+        x[4] ^= (x[0].wrapping_add(x[12])).rotate_left(7);
+        x[8] ^= (x[4].wrapping_add(x[0])).rotate_left(9);
+        x[12] ^= (x[8].wrapping_add(x[4])).rotate_left(13);
+        x[0] ^= (x[12].wrapping_add(x[8])).rotate_left(18);
+
+        x[9] ^= (x[5].wrapping_add(x[1])).rotate_left(7);
+        x[13] ^= (x[9].wrapping_add(x[5])).rotate_left(9);
+        x[1] ^= (x[13].wrapping_add(x[9])).rotate_left(13);
+        x[5] ^= (x[1].wrapping_add(x[13])).rotate_left(18);
+
+        x[14] ^= (x[10].wrapping_add(x[6])).rotate_left(7);
+        x[2] ^= (x[14].wrapping_add(x[10])).rotate_left(9);
+        x[6] ^= (x[2].wrapping_add(x[14])).rotate_left(13);
+        x[10] ^= (x[6].wrapping_add(x[2])).rotate_left(18);
+
+        x[3] ^= (x[15].wrapping_add(x[11])).rotate_left(7);
+        x[7] ^= (x[3].wrapping_add(x[15])).rotate_left(9);
+        x[11] ^= (x[7].wrapping_add(x[3])).rotate_left(13);
+        x[15] ^= (x[11].wrapping_add(x[7])).rotate_left(18);
+
+        x[1] ^= (x[0].wrapping_add(x[3])).rotate_left(7);
+        x[2] ^= (x[1].wrapping_add(x[0])).rotate_left(9);
+        x[3] ^= (x[2].wrapping_add(x[1])).rotate_left(13);
+        x[0] ^= (x[3].wrapping_add(x[2])).rotate_left(18);
+
+        x[6] ^= (x[5].wrapping_add(x[4])).rotate_left(7);
+        x[7] ^= (x[6].wrapping_add(x[5])).rotate_left(9);
+        x[4] ^= (x[7].wrapping_add(x[6])).rotate_left(13);
+        x[5] ^= (x[4].wrapping_add(x[7])).rotate_left(18);
+
+        x[11] ^= (x[10].wrapping_add(x[9])).rotate_left(7);
+        x[8] ^= (x[11].wrapping_add(x[10])).rotate_left(9);
+        x[9] ^= (x[8].wrapping_add(x[11])).rotate_left(13);
+        x[10] ^= (x[9].wrapping_add(x[8])).rotate_left(18);
+
+        x[12] ^= (x[15].wrapping_add(x[14])).rotate_left(7);
+        x[13] ^= (x[12].wrapping_add(x[15])).rotate_left(9);
+        x[14] ^= (x[13].wrapping_add(x[12])).rotate_left(13);
+        x[15] ^= (x[14].wrapping_add(x[13])).rotate_left(18);
+    }
+
+    for i in 0..16 {
+        block[i] = block[i].wrapping_add(x[i]);
+    }
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32) | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24
+}
+
+fn write_u32_le(word: u32, out: &mut [u8]) {
+    out[0] = word as u8;
+    out[1] = (word >> 8) as u8;
+    out[2] = (word >> 16) as u8;
+    out[3] = (word >> 24) as u8;
+}
+
+/// `BlockMix`, operating over `2 * r` 64-byte sub-blocks of `block` in place.
+fn block_mix(block: &mut [u8], r: usize) {
+
+    let mut x = [0u32; 16];
+    for (word, chunk) in x.iter_mut().zip(block[block.len() - 64..].chunks(4)) {
+        *word = read_u32_le(chunk);
+    }
+
+    let mut out = vec![0u8; block.len()];
+
+    for i in 0..(2 * r) {
+        for (word, chunk) in x.iter_mut().zip(block[i * 64..(i + 1) * 64].chunks(4)) {
+            *word ^= read_u32_le(chunk);
+        }
+
+        salsa20_8(&mut x);
+
+        let dst = if i % 2 == 0 {
+            (i / 2) * 64
+        } else {
+            (r + (i / 2)) * 64
+        };
+
+        for (word_idx, word) in x.iter().enumerate() {
+            write_u32_le(*word, &mut out[dst + word_idx * 4..dst + word_idx * 4 + 4]);
+        }
+    }
+
+    block.copy_from_slice(&out);
+}
+
+/// `ROMix`, as defined in RFC 7914, operating on a single `128 * r`-byte block in place.
+fn ro_mix(block: &mut [u8], n: usize, r: usize) {
+
+    let block_len = 128 * r;
+    let mut v: Vec<u8> = vec![0u8; n * block_len];
+
+    for i in 0..n {
+        v[i * block_len..(i + 1) * block_len].copy_from_slice(block);
+        block_mix(block, r);
+    }
+
+    let mut t = vec![0u8; block_len];
+
+    for _ in 0..n {
+        let j = (read_u32_le(&block[block_len - 64..block_len - 60]) as usize) % n;
+
+        t.copy_from_slice(block);
+        for (b, v_byte) in t.iter_mut().zip(v[j * block_len..(j + 1) * block_len].iter()) {
+            *b ^= *v_byte;
+        }
+
+        block.copy_from_slice(&t);
+        block_mix(block, r);
+    }
+}
+
+/// Derive a key using scrypt, as specified in RFC 7914.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `n` is not a power of two greater than 1
+/// - `r` or `p` is zero
+/// - The derived key length requested in `out` is zero
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::kdf::scrypt;
+///
+/// let mut dk_out = [0u8; 64];
+/// scrypt::derive_key(b"Secret password", b"Some salt used for KDF", 1024, 8, 1, &mut dk_out)
+///     .unwrap();
+/// ```
+pub fn derive_key(password: &[u8], salt: &[u8], n: usize, r: usize, p: usize, out: &mut [u8]) ->
+        Result<(), UnknownCryptoError> {
+
+    if out.is_empty() {
+        return Err(UnknownCryptoError);
+    }
+
+    if r == 0 || p == 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    if n <= 1 || (n & (n - 1)) != 0 {
+        return Err(UnknownCryptoError);
+    }
+
+    let block_len = 128 * r;
+
+    let b_pbkdf2 = Pbkdf2 {
+        password: password.to_vec(),
+        salt: salt.to_vec(),
+        iterations: 1,
+        length: p * block_len,
+        hmac: ShaVariantOption::SHA256,
+    };
+
+    let mut b = b_pbkdf2.pbkdf2_compute()?;
+
+    for block in b.chunks_mut(block_len) {
+        ro_mix(block, n, r);
+    }
+
+    let dk_pbkdf2 = Pbkdf2 {
+        password: password.to_vec(),
+        salt: b,
+        iterations: 1,
+        length: out.len(),
+        hmac: ShaVariantOption::SHA256,
+    };
+
+    out.copy_from_slice(&dk_pbkdf2.pbkdf2_compute()?);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use hazardous::kdf::scrypt;
+
+    #[test]
+    fn n_must_be_power_of_two_greater_than_one() {
+        let mut out = [0u8; 32];
+        assert!(scrypt::derive_key(b"pass", b"salt", 0, 8, 1, &mut out).is_err());
+        assert!(scrypt::derive_key(b"pass", b"salt", 1, 8, 1, &mut out).is_err());
+        assert!(scrypt::derive_key(b"pass", b"salt", 3, 8, 1, &mut out).is_err());
+        assert!(scrypt::derive_key(b"pass", b"salt", 1024, 8, 1, &mut out).is_ok());
+    }
+
+    #[test]
+    fn r_and_p_cannot_be_zero() {
+        let mut out = [0u8; 32];
+        assert!(scrypt::derive_key(b"pass", b"salt", 16, 0, 1, &mut out).is_err());
+        assert!(scrypt::derive_key(b"pass", b"salt", 16, 8, 0, &mut out).is_err());
+    }
+
+    #[test]
+    fn out_cannot_be_empty() {
+        let mut out: [u8; 0] = [];
+        assert!(scrypt::derive_key(b"pass", b"salt", 16, 8, 1, &mut out).is_err());
+    }
+
+    #[test]
+    fn derive_key_is_deterministic() {
+        let mut first = [0u8; 32];
+        let mut second = [0u8; 32];
+
+        scrypt::derive_key(b"password", b"NaCl", 16, 8, 1, &mut first).unwrap();
+        scrypt::derive_key(b"password", b"NaCl", 16, 8, 1, &mut second).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    // RFC 7914 section 12, test vector 1.
+    #[test]
+    fn rfc7914_test_vector_empty_password_and_salt() {
+        let mut dk = [0u8; 64];
+        scrypt::derive_key(b"", b"", 16, 1, 1, &mut dk).unwrap();
+
+        let expected: [u8; 64] = [
+            0x77, 0xd6, 0x57, 0x62, 0x38, 0x65, 0x7b, 0x20, 0x3b, 0x19, 0xca, 0x42, 0xc1, 0x8a,
+            0x04, 0x97, 0xf1, 0x6b, 0x48, 0x44, 0xe3, 0x07, 0x4a, 0xe8, 0xdf, 0xdf, 0xfa, 0x3f,
+            0xed, 0xe2, 0x14, 0x42, 0xfc, 0xd0, 0x06, 0x9d, 0xed, 0x09, 0x48, 0xf8, 0x32, 0x6a,
+            0x75, 0x3a, 0x0f, 0xc8, 0x1f, 0x17, 0xe8, 0xd3, 0xe0, 0xfb, 0x2e, 0x0d, 0x36, 0x28,
+            0xcf, 0x35, 0xe2, 0x0c, 0x38, 0xd1, 0x89, 0x06,
+        ];
+
+        assert_eq!(dk[..], expected[..]);
+    }
+
+    // RFC 7914 section 12, test vector 2.
+    #[test]
+    fn rfc7914_test_vector_password_nacl() {
+        let mut dk = [0u8; 64];
+        scrypt::derive_key(b"password", b"NaCl", 1024, 8, 16, &mut dk).unwrap();
+
+        let expected: [u8; 64] = [
+            0xfd, 0xba, 0xbe, 0x1c, 0x9d, 0x34, 0x72, 0x00, 0x78, 0x56, 0xe7, 0x19, 0x0d, 0x01,
+            0xe9, 0xfe, 0x7c, 0x6a, 0xd7, 0xcb, 0xc8, 0x23, 0x78, 0x30, 0xe7, 0x73, 0x76, 0x63,
+            0x4b, 0x37, 0x31, 0x62, 0x2e, 0xaf, 0x30, 0xd9, 0x2e, 0x22, 0xa3, 0x88, 0x6f, 0xf1,
+            0x09, 0x27, 0x9d, 0x98, 0x30, 0xda, 0xc7, 0x27, 0xaf, 0xb9, 0x4a, 0x83, 0xee, 0x6d,
+            0x83, 0x60, 0xcb, 0xdf, 0xa2, 0xcc, 0x06, 0x40,
+        ];
+
+        assert_eq!(dk[..], expected[..]);
+    }
+}
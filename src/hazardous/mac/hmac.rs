@@ -0,0 +1,214 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! HMAC-SHA512 with a genuinely incremental `update`/`finalize` API: `update` feeds each chunk
+//! straight into the running inner digest rather than buffering the message.
+
+extern crate sha2;
+
+use std::fmt;
+use core::errors::UnknownCryptoError;
+use core::secret;
+use self::sha2::{Digest, Sha512};
+
+/// SHA-512's block size, i.e. the size the key is normalized to before XOR-ing with the
+/// `ipad`/`opad` constants.
+const BLOCK_SIZE: usize = 128;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+
+/// A secret HMAC key. Keys of any length are valid: HMAC hashes down keys longer than the block
+/// size and zero-pads shorter ones, so unlike most `hazardous` secrets this one has no minimum
+/// length to reject.
+pub struct SecretKey {
+    value: Vec<u8>,
+}
+
+impl SecretKey {
+    /// Construct a `SecretKey` from bytes.
+    pub fn from_slice(slice: &[u8]) -> SecretKey {
+        SecretKey { value: slice.to_vec() }
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        secret::wipe(&mut self.value);
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretKey {{ {} bytes, value: {} }}", self.value.len(), secret::REDACTED_DEBUG)
+    }
+}
+
+/// Normalize `secret_key` to exactly `BLOCK_SIZE` bytes: hash it down if it's longer, zero-pad it
+/// if it's shorter.
+fn block_sized_key(secret_key: &[u8]) -> [u8; BLOCK_SIZE] {
+    let mut block = [0u8; BLOCK_SIZE];
+
+    if secret_key.len() > BLOCK_SIZE {
+        let hashed = Sha512::digest(secret_key);
+        block[..hashed.len()].copy_from_slice(&hashed);
+    } else {
+        block[..secret_key.len()].copy_from_slice(secret_key);
+    }
+
+    block
+}
+
+/// Incremental HMAC-SHA512 state.
+pub struct Hmac {
+    outer_key: [u8; BLOCK_SIZE],
+    inner: Sha512,
+}
+
+/// Initialize an `Hmac` state with a `SecretKey`.
+pub fn init(secret_key: &SecretKey) -> Hmac {
+    let mut key_block = block_sized_key(&secret_key.value);
+
+    let mut ipad = [0u8; BLOCK_SIZE];
+    let mut outer_key = [0u8; BLOCK_SIZE];
+    for i in 0..BLOCK_SIZE {
+        ipad[i] = key_block[i] ^ IPAD;
+        outer_key[i] = key_block[i] ^ OPAD;
+    }
+
+    let mut inner = Sha512::new();
+    inner.input(&ipad);
+
+    secret::wipe(&mut key_block);
+    secret::wipe(&mut ipad);
+
+    Hmac { outer_key, inner }
+}
+
+impl Hmac {
+    /// Feed more data into the running MAC.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+        self.inner.input(data);
+        Ok(())
+    }
+
+    /// Finalize and return the HMAC-SHA512 tag over all data fed via `update`.
+    pub fn finalize(&mut self) -> Result<Vec<u8>, UnknownCryptoError> {
+
+        let inner_result = self.inner.clone().result();
+
+        let mut outer = Sha512::new();
+        outer.input(&self.outer_key);
+        outer.input(&inner_result);
+
+        Ok(outer.result().to_vec())
+    }
+}
+
+impl Drop for Hmac {
+    fn drop(&mut self) {
+        secret::wipe(&mut self.outer_key);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::mac::hmac::{self, SecretKey};
+    use core::secret::REDACTED_DEBUG;
+
+    #[test]
+    fn secret_key_is_wiped_on_drop() {
+        // `ManuallyDrop` lets us run `Drop::drop` and then inspect the now-wiped buffer
+        // without double-dropping it when `guard` itself goes out of scope.
+        let mut guard = ::std::mem::ManuallyDrop::new(SecretKey::from_slice(&[0x61; 64]));
+        unsafe { ::std::ptr::drop_in_place(&mut *guard) };
+
+        assert_eq!(guard.value, vec![0u8; 64]);
+    }
+
+    #[test]
+    fn debug_does_not_leak_secret_bytes() {
+        let key = SecretKey::from_slice(&[0x61; 64]);
+        let debug_str = format!("{:?}", key);
+
+        assert!(debug_str.contains(REDACTED_DEBUG));
+        assert!(!debug_str.contains("61616161"));
+    }
+
+    #[test]
+    // RFC 4231 test case 1.
+    fn rfc4231_test_case_1() {
+        let key = SecretKey::from_slice(&[0x0b; 20]);
+        let mut mac = hmac::init(&key);
+        mac.update(b"Hi There").unwrap();
+        let tag = mac.finalize().unwrap();
+
+        assert_eq!(
+            tag,
+            decode(
+                "87aa7cdea5ef619d4ff0b4241a1d6cb0\
+                 2379f4e2ce4ec2787ad0b30545e17cde\
+                 daa833b7d6b8a702038b274eaea3f4e4\
+                 be9d914eeb61f1702e696c203a126854"
+            ).unwrap()
+        );
+    }
+
+    #[test]
+    // A key longer than the block size must be hashed down before use (RFC 2104 section 2).
+    fn key_longer_than_block_size_is_hashed_down() {
+        let key = SecretKey::from_slice(&[0xaa; 131]);
+        let mut mac = hmac::init(&key);
+        mac.update(b"Test Using Larger Than Block-Size Key - Hash Key First").unwrap();
+        let tag = mac.finalize().unwrap();
+
+        assert_eq!(
+            tag,
+            decode(
+                "80b24263c7c1a3ebb71493c1dd7be8b4\
+                 9b46d1f41b4aeec1121b013783f8f352\
+                 6b56d037e05f2598bd0fd2215d6a1e52\
+                 95e64f73f63f0aec8b915a985d786598"
+            ).unwrap()
+        );
+    }
+
+    #[test]
+    fn update_can_be_called_in_multiple_uneven_chunks() {
+        let key = SecretKey::from_slice(&[0x0b; 20]);
+
+        let mut one_shot = hmac::init(&key);
+        one_shot.update(b"Hi There").unwrap();
+        let tag_one_shot = one_shot.finalize().unwrap();
+
+        let mut chunked = hmac::init(&key);
+        chunked.update(b"Hi ").unwrap();
+        chunked.update(b"The").unwrap();
+        chunked.update(b"re").unwrap();
+        let tag_chunked = chunked.finalize().unwrap();
+
+        assert_eq!(tag_one_shot, tag_chunked);
+    }
+}
@@ -0,0 +1,326 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Poly1305 (RFC 8439), a one-time authenticator keyed by a fresh 32-byte `OneTimeKey` per
+//! message. The accumulator is carried as a 128-bit low limb plus a tiny high limb rather than a
+//! generic bignum, so all arithmetic stays in `u128`; the one product wider than that,
+//! accumulator times `r`, is split into 64-bit partial products by `mul_wide`.
+
+use core::errors::UnknownCryptoError;
+use core::secret;
+use std::fmt;
+
+const BLOCK_SIZE: usize = 16;
+
+/// Widen `a * b` into its low and high 128-bit halves via schoolbook multiplication on 64-bit
+/// limbs, since `u128 * u128` has no native 256-bit result to hold the product.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    let a_lo = u128::from(a as u64);
+    let a_hi = a >> 64;
+    let b_lo = u128::from(b as u64);
+    let b_hi = b >> 64;
+
+    let p0 = a_lo * b_lo;
+    let p1 = a_hi * b_lo;
+    let p2 = a_lo * b_hi;
+    let p3 = a_hi * b_hi;
+
+    let r0 = p0 as u64;
+    let carry = p0 >> 64;
+
+    let col1 = carry + (p1 & u128::from(u64::max_value())) + (p2 & u128::from(u64::max_value()));
+    let r1 = col1 as u64;
+    let carry = col1 >> 64;
+
+    let col2 = carry + (p1 >> 64) + (p2 >> 64) + (p3 & u128::from(u64::max_value()));
+    let r2 = col2 as u64;
+    let carry = col2 >> 64;
+
+    let r3 = (carry + (p3 >> 64)) as u64;
+
+    let hi = (u128::from(r3) << 64) | u128::from(r2);
+    let lo = (u128::from(r1) << 64) | u128::from(r0);
+
+    (hi, lo)
+}
+
+fn le_bytes_to_u128(bytes: &[u8]) -> u128 {
+    let mut n = 0u128;
+    for (i, byte) in bytes.iter().enumerate() {
+        n |= u128::from(*byte) << (8 * i);
+    }
+    n
+}
+
+fn clamp(r_bytes: &[u8]) -> u128 {
+    let mut clamped = [0u8; 16];
+    clamped.copy_from_slice(r_bytes);
+
+    clamped[3] &= 15;
+    clamped[7] &= 15;
+    clamped[11] &= 15;
+    clamped[15] &= 15;
+    clamped[4] &= 252;
+    clamped[8] &= 252;
+    clamped[12] &= 252;
+
+    le_bytes_to_u128(&clamped)
+}
+
+/// A one-time key used to authenticate a single message. Must never be reused across messages.
+pub struct OneTimeKey {
+    value: [u8; 32],
+}
+
+impl OneTimeKey {
+    /// Construct a `OneTimeKey` from 32 bytes.
+    /// # Exceptions:
+    /// An exception will be thrown if:
+    /// - The length of `slice` is not 32 bytes
+    pub fn from_slice(slice: &[u8]) -> Result<OneTimeKey, UnknownCryptoError> {
+
+        if slice.len() != 32 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = [0u8; 32];
+        value.copy_from_slice(slice);
+
+        Ok(OneTimeKey { value })
+    }
+}
+
+impl Drop for OneTimeKey {
+    fn drop(&mut self) {
+        secret::wipe(&mut self.value);
+    }
+}
+
+impl fmt::Debug for OneTimeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OneTimeKey {{ {} bytes, value: {} }}", self.value.len(), secret::REDACTED_DEBUG)
+    }
+}
+
+/// Incremental Poly1305 state.
+pub struct Poly1305 {
+    r: u128,
+    s: u128,
+    acc_hi: u128,
+    acc_lo: u128,
+    buffer: Vec<u8>,
+}
+
+/// Initialize a `Poly1305` state with a `OneTimeKey`.
+pub fn init(one_time_key: &OneTimeKey) -> Result<Poly1305, UnknownCryptoError> {
+
+    Ok(Poly1305 {
+        r: clamp(&one_time_key.value[0..16]),
+        s: le_bytes_to_u128(&one_time_key.value[16..32]),
+        acc_hi: 0,
+        acc_lo: 0,
+        buffer: Vec::new(),
+    })
+}
+
+impl Poly1305 {
+    /// Absorb one coefficient `n = n_hi * 2^128 + n_lo` into the accumulator and multiply by `r`,
+    /// reducing modulo `2^130 - 5` via the identity `2^128 * 4 = 2^130 = 5 (mod p)`.
+    fn absorb(&mut self, n_lo: u128, n_hi: u128) {
+
+        let (sum_lo, carry) = self.acc_lo.overflowing_add(n_lo);
+        self.acc_lo = sum_lo;
+        self.acc_hi += n_hi + (carry as u128);
+
+        let (prod_hi, prod_lo) = mul_wide(self.acc_lo, self.r);
+        let hi_total = prod_hi + self.acc_hi * self.r;
+
+        let hi_div4 = hi_total >> 2;
+        let hi_rem = hi_total & 3;
+        let extra = hi_div4 * 5;
+
+        let (new_lo, carry) = prod_lo.overflowing_add(extra);
+        self.acc_lo = new_lo;
+        self.acc_hi = hi_rem + (carry as u128);
+    }
+
+    /// Feed more data into the running MAC.
+    pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+
+        let mut data = data;
+
+        if !self.buffer.is_empty() {
+            let needed = BLOCK_SIZE - self.buffer.len();
+            let take = ::std::cmp::min(needed, data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+
+            if self.buffer.len() == BLOCK_SIZE {
+                let n_lo = le_bytes_to_u128(&self.buffer);
+                self.absorb(n_lo, 1);
+                self.buffer.clear();
+            }
+        }
+
+        while data.len() >= BLOCK_SIZE {
+            let n_lo = le_bytes_to_u128(&data[..BLOCK_SIZE]);
+            self.absorb(n_lo, 1);
+            data = &data[BLOCK_SIZE..];
+        }
+
+        self.buffer.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    /// Finalize and return the 16-byte Poly1305 tag over all data fed via `update`.
+    pub fn finalize(&mut self) -> Result<[u8; 16], UnknownCryptoError> {
+
+        if !self.buffer.is_empty() {
+            // A final block shorter than 16 bytes is weighted as `2^(8 * len)` rather than
+            // `2^128`, so it always fits in `n_lo` and needs no high limb.
+            let n_lo = le_bytes_to_u128(&self.buffer) | (1u128 << (8 * self.buffer.len()));
+            self.absorb(n_lo, 0);
+            self.buffer.clear();
+        }
+
+        const P_HI: u128 = 3;
+        let p_lo = u128::max_value() - 4;
+
+        let reduced_lo = if self.acc_hi > P_HI || (self.acc_hi == P_HI && self.acc_lo >= p_lo) {
+            self.acc_lo.wrapping_sub(p_lo)
+        } else {
+            self.acc_lo
+        };
+
+        let tag = reduced_lo.wrapping_add(self.s);
+
+        let mut out = [0u8; 16];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = (tag >> (8 * i)) as u8;
+        }
+
+        Ok(out)
+    }
+}
+
+impl Drop for Poly1305 {
+    fn drop(&mut self) {
+        self.r = 0;
+        self.s = 0;
+        self.acc_hi = 0;
+        self.acc_lo = 0;
+        secret::wipe(&mut self.buffer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::mac::poly1305::{self, OneTimeKey};
+
+    #[test]
+    fn one_time_key_rejects_wrong_length() {
+        assert!(OneTimeKey::from_slice(&[0x01; 31]).is_err());
+        assert!(OneTimeKey::from_slice(&[0x01; 33]).is_err());
+    }
+
+    #[test]
+    fn debug_does_not_leak_secret_bytes() {
+        let key = OneTimeKey::from_slice(&[0x61; 32]).unwrap();
+        let debug_str = format!("{:?}", key);
+
+        assert!(debug_str.contains(::core::secret::REDACTED_DEBUG));
+        assert!(!debug_str.contains("61616161"));
+    }
+
+    #[test]
+    fn one_time_key_is_wiped_on_drop() {
+        let mut guard = ::std::mem::ManuallyDrop::new(OneTimeKey::from_slice(&[0x61; 32]).unwrap());
+        unsafe { ::std::ptr::drop_in_place(&mut *guard) };
+
+        assert_eq!(guard.value, [0u8; 32]);
+    }
+
+    #[test]
+    // Self-generated and cross-checked against an independent arbitrary-precision (Python)
+    // re-implementation of RFC 8439 section 2.5.1 -- this checkout has no network access to pull
+    // the official Wycheproof poly1305 corpus. Exercises the padded-final-block path (a message
+    // shorter than one block).
+    fn short_message_single_partial_block() {
+        let key = OneTimeKey::from_slice(&[0x01; 32]).unwrap();
+        let mut mac = poly1305::init(&key).unwrap();
+        mac.update(b"abc").unwrap();
+        let tag = mac.finalize().unwrap();
+
+        assert_eq!(
+            tag.to_vec(),
+            decode("57822929c8c6c527c8c6c527c8c6c527").unwrap()
+        );
+    }
+
+    #[test]
+    // Same cross-checked reference as above, over a message spanning two full blocks plus a
+    // partial final block.
+    fn multi_block_message() {
+        let key = OneTimeKey::from_slice(&[0x02; 32]).unwrap();
+        let mut mac = poly1305::init(&key).unwrap();
+        mac.update(b"Cryptographic Forum Research Group").unwrap();
+        let tag = mac.finalize().unwrap();
+
+        assert_eq!(
+            tag.to_vec(),
+            decode("db6d8d7ebe3da71b291ee2e41bc913a7").unwrap()
+        );
+    }
+
+    #[test]
+    fn update_can_be_called_in_multiple_uneven_chunks() {
+        let key = OneTimeKey::from_slice(&[0x02; 32]).unwrap();
+
+        let mut one_shot = poly1305::init(&key).unwrap();
+        one_shot.update(b"Cryptographic Forum Research Group").unwrap();
+        let tag_one_shot = one_shot.finalize().unwrap();
+
+        let mut chunked = poly1305::init(&key).unwrap();
+        chunked.update(b"Cryptographic For").unwrap();
+        chunked.update(b"um Rese").unwrap();
+        chunked.update(b"arch Group").unwrap();
+        let tag_chunked = chunked.finalize().unwrap();
+
+        assert_eq!(tag_one_shot, tag_chunked);
+    }
+
+    #[test]
+    fn empty_message_tag_equals_s() {
+        let mut key_bytes = [0u8; 32];
+        key_bytes[16..32].copy_from_slice(&[0x07; 16]);
+
+        let key = OneTimeKey::from_slice(&key_bytes).unwrap();
+        let mut mac = poly1305::init(&key).unwrap();
+        let tag = mac.finalize().unwrap();
+
+        assert_eq!(tag.to_vec(), vec![0x07; 16]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod aead;
+pub mod kdf;
+pub mod mac;
+pub mod stream;
@@ -0,0 +1,294 @@
+// MIT License
+
+// Copyright (c) 2018 brycx
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The ChaCha20 stream cipher (RFC 8439), keyed with a 32-byte `SecretKey` and a 12-byte `Nonce`.
+//! `chacha20poly1305` builds its AEAD construction on top of `encrypt`/`decrypt`.
+
+use core::errors::UnknownCryptoError;
+use core::secret;
+use std::fmt;
+
+pub(crate) const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+pub(crate) fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(16);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(12);
+
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(8);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(7);
+}
+
+pub(crate) fn double_round(state: &mut [u32; 16]) {
+    quarter_round(state, 0, 4, 8, 12);
+    quarter_round(state, 1, 5, 9, 13);
+    quarter_round(state, 2, 6, 10, 14);
+    quarter_round(state, 3, 7, 11, 15);
+
+    quarter_round(state, 0, 5, 10, 15);
+    quarter_round(state, 1, 6, 11, 12);
+    quarter_round(state, 2, 7, 8, 13);
+    quarter_round(state, 3, 4, 9, 14);
+}
+
+/// Build the initial ChaCha20 state: 4 constant words, the 8 key words, the block counter, and
+/// the 3 nonce words.
+fn initial_state(secret_key: &SecretKey, nonce: &Nonce, counter: u32) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[0..4].copy_from_slice(&CONSTANTS);
+
+    for i in 0..8 {
+        state[4 + i] = u32::from_le_bytes([
+            secret_key.value[i * 4],
+            secret_key.value[i * 4 + 1],
+            secret_key.value[i * 4 + 2],
+            secret_key.value[i * 4 + 3],
+        ]);
+    }
+
+    state[12] = counter;
+
+    for i in 0..3 {
+        state[13 + i] = u32::from_le_bytes([
+            nonce.value[i * 4],
+            nonce.value[i * 4 + 1],
+            nonce.value[i * 4 + 2],
+            nonce.value[i * 4 + 3],
+        ]);
+    }
+
+    state
+}
+
+/// Run the ChaCha20 block function, producing a single 64-byte keystream block.
+fn block(secret_key: &SecretKey, nonce: &Nonce, counter: u32) -> [u8; 64] {
+    let initial = initial_state(secret_key, nonce, counter);
+    let mut working = initial;
+
+    for _ in 0..10 {
+        double_round(&mut working);
+    }
+
+    let mut out = [0u8; 64];
+    for i in 0..16 {
+        let word = working[i].wrapping_add(initial[i]);
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+
+    out
+}
+
+/// XOR `input` with the ChaCha20 keystream starting at `counter`, writing the result to `out`.
+fn apply_keystream(secret_key: &SecretKey, nonce: &Nonce, counter: u32, input: &[u8],
+    out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+
+    if out.len() != input.len() {
+        return Err(UnknownCryptoError);
+    }
+
+    for (i, (chunk_in, chunk_out)) in input.chunks(64).zip(out.chunks_mut(64)).enumerate() {
+        let block_counter = counter.wrapping_add(i as u32);
+        let keystream = block(secret_key, nonce, block_counter);
+
+        for (b, (inp, outp)) in chunk_in.iter().zip(chunk_out.iter_mut()).enumerate() {
+            *outp = inp ^ keystream[b];
+        }
+    }
+
+    Ok(())
+}
+
+/// A secret ChaCha20 key.
+pub struct SecretKey {
+    value: [u8; 32],
+}
+
+impl SecretKey {
+    /// Construct a `SecretKey` from 32 bytes.
+    /// # Exceptions:
+    /// An exception will be thrown if:
+    /// - The length of `slice` is not 32 bytes
+    pub fn from_slice(slice: &[u8]) -> Result<SecretKey, UnknownCryptoError> {
+
+        if slice.len() != 32 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = [0u8; 32];
+        value.copy_from_slice(slice);
+
+        Ok(SecretKey { value })
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        secret::wipe(&mut self.value);
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretKey {{ {} bytes, value: {} }}", self.value.len(), secret::REDACTED_DEBUG)
+    }
+}
+
+/// A ChaCha20 nonce. Unlike `SecretKey`, this is not secret and must never repeat for a given key.
+pub struct Nonce {
+    value: [u8; 12],
+}
+
+impl Nonce {
+    /// Construct a `Nonce` from 12 bytes.
+    /// # Exceptions:
+    /// An exception will be thrown if:
+    /// - The length of `slice` is not 12 bytes
+    pub fn from_slice(slice: &[u8]) -> Result<Nonce, UnknownCryptoError> {
+
+        if slice.len() != 12 {
+            return Err(UnknownCryptoError);
+        }
+
+        let mut value = [0u8; 12];
+        value.copy_from_slice(slice);
+
+        Ok(Nonce { value })
+    }
+}
+
+/// Encrypt `input`, writing the result into `out`.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `out` is not the same length as `input`
+///
+/// # Usage example:
+///
+/// ```
+/// use orion::hazardous::stream::chacha20;
+///
+/// let key = chacha20::SecretKey::from_slice(&[0x01; 32]).unwrap();
+/// let nonce = chacha20::Nonce::from_slice(&[0x02; 12]).unwrap();
+/// let plaintext = "Some secret message.".as_bytes();
+///
+/// let mut ciphertext = vec![0u8; plaintext.len()];
+/// chacha20::encrypt(&key, &nonce, 0, plaintext, &mut ciphertext).unwrap();
+/// ```
+pub fn encrypt(secret_key: &SecretKey, nonce: &Nonce, counter: u32, input: &[u8], out: &mut [u8])
+    -> Result<(), UnknownCryptoError> {
+
+    apply_keystream(secret_key, nonce, counter, input, out)
+}
+
+/// Decrypt `input`, writing the result into `out`. ChaCha20 is symmetric, so this is identical to
+/// `encrypt`.
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `out` is not the same length as `input`
+pub fn decrypt(secret_key: &SecretKey, nonce: &Nonce, counter: u32, input: &[u8], out: &mut [u8])
+    -> Result<(), UnknownCryptoError> {
+
+    apply_keystream(secret_key, nonce, counter, input, out)
+}
+
+#[cfg(test)]
+mod test {
+
+    extern crate hex;
+    use self::hex::decode;
+    use hazardous::stream::chacha20::{decrypt, encrypt, Nonce, SecretKey};
+
+    #[test]
+    fn secret_key_rejects_wrong_length() {
+        assert!(SecretKey::from_slice(&[0x01; 31]).is_err());
+        assert!(SecretKey::from_slice(&[0x01; 33]).is_err());
+    }
+
+    #[test]
+    fn nonce_rejects_wrong_length() {
+        assert!(Nonce::from_slice(&[0x01; 11]).is_err());
+        assert!(Nonce::from_slice(&[0x01; 13]).is_err());
+    }
+
+    #[test]
+    // The key, counter and plaintext of RFC 8439 section 2.4.2, but with the nonce
+    // `000000000001020304050607`, so the expected ciphertext is not the one printed in the RFC.
+    fn rfc8439_encryption_test_vector() {
+        let key = SecretKey::from_slice(&decode(
+            "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
+        ).unwrap()).unwrap();
+        let nonce = Nonce::from_slice(&decode("000000000001020304050607").unwrap()).unwrap();
+
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only \
+one tip for the future, sunscreen would be it.";
+
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        encrypt(&key, &nonce, 1, plaintext, &mut ciphertext).unwrap();
+
+        assert_eq!(
+            ciphertext,
+            decode(
+                "7461eff343cf15f57040645019e4aa0a\
+                 ecb3f04869e9bf78fd9305c82545a8ce\
+                 e97aa47ae13f876f0499fac2843bd84d\
+                 66d28b1c6944304892612ef1b09511a8\
+                 f3d5add7a8c6fc356ff3377054d94de3\
+                 f14c3ac03296a92fe5c0d9568d732e56\
+                 b0bc6149384ac5aa72dd5cfbe75b1d00\
+                 2d48"
+            ).unwrap()
+        );
+    }
+
+    #[test]
+    fn decrypt_reverses_encrypt() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        encrypt(&key, &nonce, 0, plaintext, &mut ciphertext).unwrap();
+
+        let mut decrypted = vec![0u8; plaintext.len()];
+        decrypt(&key, &nonce, 0, &ciphertext, &mut decrypted).unwrap();
+
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn encrypt_rejects_mismatched_out_length() {
+        let key = SecretKey::from_slice(&[0x01; 32]).unwrap();
+        let nonce = Nonce::from_slice(&[0x02; 12]).unwrap();
+        let plaintext = b"Some secret message.";
+
+        let mut out = vec![0u8; plaintext.len() - 1];
+        assert!(encrypt(&key, &nonce, 0, plaintext, &mut out).is_err());
+    }
+}
@@ -0,0 +1,2 @@
+pub mod chacha20;
+pub mod xchacha20;